@@ -33,26 +33,127 @@ use crate::{Context, Tcti, NO_SESSIONS};
 use log::error;
 use std::convert::{TryFrom, TryInto};
 
-/// Structure offering an abstracted programming experience.
+/// The TPM hierarchy a `TransientObjectContext` is rooted in.
 ///
-/// The `TransientObjectContext` makes use of a root key from which the other, client-controlled
-/// keyes are derived.
+/// This selects both the `ESYS_TR` handle alias used for in-TPM operations (creating the root
+/// key, authenticating the hierarchy) and the raw `TPM2_RH` handle used when an object is not
+/// loaded into the ESAPI, such as in `Context::load_external_public`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hierarchy {
+    Owner,
+    Platform,
+    Endorsement,
+    Null,
+}
+
+impl Hierarchy {
+    fn esys_handle(self) -> ESYS_TR {
+        match self {
+            Hierarchy::Owner => ESYS_TR_RH_OWNER,
+            Hierarchy::Platform => ESYS_TR_RH_PLATFORM,
+            Hierarchy::Endorsement => ESYS_TR_RH_ENDORSEMENT,
+            Hierarchy::Null => ESYS_TR_RH_NULL,
+        }
+    }
+
+    fn tpm_handle(self) -> TPM2_HANDLE {
+        match self {
+            Hierarchy::Owner => TPM2_RH_OWNER,
+            Hierarchy::Platform => TPM2_RH_PLATFORM,
+            Hierarchy::Endorsement => TPM2_RH_ENDORSEMENT,
+            Hierarchy::Null => TPM2_RH_NULL,
+        }
+    }
+}
+
+impl Default for Hierarchy {
+    fn default() -> Self {
+        Hierarchy::Owner
+    }
+}
+
+/// Builder for a `TransientObjectContext`.
 ///
-/// Currently, only functionality necessary for RSA key creation and usage (for signing and
-/// verifying signatures) is implemented.
-#[allow(clippy::module_name_repetitions)]
+/// Allows configuring the TCTI, the hierarchy the root key is created under, the root key's
+/// size and authentication size, the hierarchy's authentication value, the session cipher used
+/// to encrypt the main session and the hash algorithm used for that session, before constructing
+/// the context with `build`. Unconfigured parameters default to the values previously hard-coded
+/// in `TransientObjectContext::new`: Device TCTI, Owner hierarchy, 2,048-bit root key, 32-byte
+/// root key authentication, AES-256-CFB session cipher and SHA256 session hash.
 #[derive(Debug)]
-pub struct TransientObjectContext {
-    context: Context,
-    root_key_handle: ESYS_TR,
+pub struct TransientObjectContextBuilder {
+    tcti: Tcti,
+    hierarchy: Hierarchy,
+    root_key_size: usize,
+    root_key_auth_size: usize,
+    hierarchy_auth: Vec<u8>,
+    default_context_cipher: TPMT_SYM_DEF,
+    session_hash_alg: TPM2_ALG_ID,
 }
 
-impl TransientObjectContext {
-    /// Create a new `TransientObjectContext`.
-    ///
-    /// The root key is created as a primary key in the Owner hierarchy and thus authentication is
-    /// needed for the hierarchy. The authentication value is generated by the TPM itself, with a
-    /// length provided as a parameter, and never exposed outside the context.
+impl Default for TransientObjectContextBuilder {
+    fn default() -> Self {
+        TransientObjectContextBuilder {
+            tcti: Tcti::Device,
+            hierarchy: Hierarchy::default(),
+            root_key_size: 2048,
+            root_key_auth_size: 32,
+            hierarchy_auth: vec![],
+            default_context_cipher: utils::TpmtSymDefBuilder::aes_256_cfb(),
+            session_hash_alg: TPM2_ALG_SHA256,
+        }
+    }
+}
+
+impl TransientObjectContextBuilder {
+    /// Create a new builder, pre-populated with the same defaults as `TransientObjectContext::new`.
+    pub fn new() -> Self {
+        TransientObjectContextBuilder::default()
+    }
+
+    /// Set the TCTI the context will communicate with the TPM through.
+    pub fn with_tcti(mut self, tcti: Tcti) -> Self {
+        self.tcti = tcti;
+        self
+    }
+
+    /// Set the hierarchy the root key is created under.
+    pub fn with_hierarchy(mut self, hierarchy: Hierarchy) -> Self {
+        self.hierarchy = hierarchy;
+        self
+    }
+
+    /// Set the size, in bits, of the root key.
+    pub fn with_root_key_size(mut self, root_key_size: usize) -> Self {
+        self.root_key_size = root_key_size;
+        self
+    }
+
+    /// Set the size, in bytes, of the root key's authentication value.
+    pub fn with_root_key_auth_size(mut self, root_key_auth_size: usize) -> Self {
+        self.root_key_auth_size = root_key_auth_size;
+        self
+    }
+
+    /// Set the authentication value of the hierarchy the root key is created under.
+    pub fn with_hierarchy_auth(mut self, hierarchy_auth: Vec<u8>) -> Self {
+        self.hierarchy_auth = hierarchy_auth;
+        self
+    }
+
+    /// Set the symmetric cipher used to encrypt the context's main session.
+    pub fn with_default_context_cipher(mut self, default_context_cipher: TPMT_SYM_DEF) -> Self {
+        self.default_context_cipher = default_context_cipher;
+        self
+    }
+
+    /// Set the hash algorithm used for the context's main session.
+    pub fn with_session_hash_alg(mut self, session_hash_alg: TPM2_ALG_ID) -> Self {
+        self.session_hash_alg = session_hash_alg;
+        self
+    }
+
+    /// Build the `TransientObjectContext` out of the parameters set so far.
     ///
     /// # Safety
     /// * it is the responsibility of the client to ensure that the context can be initialized
@@ -68,32 +169,27 @@ impl TransientObjectContext {
     /// `Context::set_handle_auth`
     /// * if the root key authentication size is given greater than 32 or if the root key size is
     /// not 1024 or 2048, a `WrongParamSize` wrapper error is returned
-    pub unsafe fn new(
-        tcti: Tcti,
-        root_key_size: usize,
-        root_key_auth_size: usize,
-        owner_hierarchy_auth: &[u8],
-    ) -> Result<Self> {
-        if root_key_auth_size > 32 {
+    pub unsafe fn build(self) -> Result<TransientObjectContext> {
+        if self.root_key_auth_size > 32 {
             return Err(Error::local_error(ErrorKind::WrongParamSize));
         }
-        if root_key_size != 1024 && root_key_size != 2048 {
+        if self.root_key_size != 1024 && self.root_key_size != 2048 {
             error!("The reference implementation only supports key sizes of 1,024 and 2,048 bits.");
             return Err(Error::local_error(ErrorKind::WrongParamSize));
         }
-        let mut context = Context::new(tcti)?;
-        let root_key_auth: Vec<u8> = if root_key_auth_size > 0 {
-            context.get_random(root_key_auth_size)?
+        let mut context = Context::new(self.tcti)?;
+        let root_key_auth: Vec<u8> = if self.root_key_auth_size > 0 {
+            context.get_random(self.root_key_auth_size)?
         } else {
             vec![]
         };
-        if !owner_hierarchy_auth.is_empty() {
-            context.set_handle_auth(ESYS_TR_RH_OWNER, owner_hierarchy_auth)?;
+        if !self.hierarchy_auth.is_empty() {
+            context.set_handle_auth(self.hierarchy.esys_handle(), &self.hierarchy_auth)?;
         }
 
         let root_key_handle = context.create_primary_key(
-            ESYS_TR_RH_OWNER,
-            &get_rsa_public(true, true, false, root_key_size.try_into().unwrap()), // should not fail on supported targets, given the checks above
+            self.hierarchy.esys_handle(),
+            &get_rsa_public(true, true, false, self.root_key_size.try_into().unwrap()), // should not fail on supported targets, given the checks above
             &root_key_auth,
             &[],
             &[],
@@ -106,8 +202,8 @@ impl TransientObjectContext {
             ESYS_TR_NONE,
             &[],
             TPM2_SE_HMAC,
-            utils::TpmtSymDefBuilder::aes_256_cfb(),
-            TPM2_ALG_SHA256,
+            self.default_context_cipher,
+            self.session_hash_alg,
         )?;
         let (old_session, _, _) = context.sessions();
         context.set_sessions((new_session, ESYS_TR_NONE, ESYS_TR_NONE));
@@ -115,47 +211,109 @@ impl TransientObjectContext {
         Ok(TransientObjectContext {
             context,
             root_key_handle,
+            hierarchy: self.hierarchy,
         })
     }
+}
 
-    /// Create a new RSA signing key.
+/// Structure offering an abstracted programming experience.
+///
+/// The `TransientObjectContext` makes use of a root key from which the other, client-controlled
+/// keyes are derived.
+///
+/// Functionality is provided for RSA and ECC (NIST P-256) key creation, signing and verifying
+/// signatures, and RSA OAEP encryption/decryption, all rooted in a configurable hierarchy (see
+/// `TransientObjectContextBuilder`).
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub struct TransientObjectContext {
+    context: Context,
+    root_key_handle: ESYS_TR,
+    hierarchy: Hierarchy,
+}
+
+impl TransientObjectContext {
+    /// Create a new `TransientObjectContext`.
+    ///
+    /// The root key is created as a primary key in the Owner hierarchy and thus authentication is
+    /// needed for the hierarchy. The authentication value is generated by the TPM itself, with a
+    /// length provided as a parameter, and never exposed outside the context.
+    ///
+    /// This is a thin wrapper around `TransientObjectContextBuilder` for the common case; use the
+    /// builder directly to target a different hierarchy or session cipher/hash.
+    ///
+    /// # Safety
+    /// * it is the responsibility of the client to ensure that the context can be initialized
+    /// safely, threading-wise
+    ///
+    /// # Constraints
+    /// * `root_key_size` must be 1024 or 2048
+    /// * `root_key_auth_size` must be at most 32
+    ///
+    /// # Errors
+    /// * errors are returned if any method calls return an error: `Context::get_random`,
+    /// `Context::start_auth_session`, `Context::create_primary_key`, `Context::flush_context`,
+    /// `Context::set_handle_auth`
+    /// * if the root key authentication size is given greater than 32 or if the root key size is
+    /// not 1024 or 2048, a `WrongParamSize` wrapper error is returned
+    pub unsafe fn new(
+        tcti: Tcti,
+        root_key_size: usize,
+        root_key_auth_size: usize,
+        owner_hierarchy_auth: &[u8],
+    ) -> Result<Self> {
+        TransientObjectContextBuilder::new()
+            .with_tcti(tcti)
+            .with_root_key_size(root_key_size)
+            .with_root_key_auth_size(root_key_auth_size)
+            .with_hierarchy_auth(owner_hierarchy_auth.to_vec())
+            .build()
+    }
+
+    /// Create a new key from the given `KeyParams`.
     ///
-    /// The key is created with most parameters defaulted as described for the `get_rsa_public`
-    /// method. The authentication value is generated by the TPM and returned along with the key
-    /// context.
+    /// This is the generic key-creation entry point: the scheme captured in `key_params` is
+    /// baked into the key's `TPMT_PUBLIC`, and its object attributes are set for signing or for
+    /// encryption/decryption depending on whether that scheme is a signing scheme. Later use
+    /// (`sign`, `rsa_encrypt`/`rsa_decrypt`) takes the scheme as an explicit argument, which must
+    /// be the same scheme given here. The
+    /// authentication value is generated by the TPM and returned along with the key context, or
+    /// `None` if `auth_size` is zero.
     ///
     /// # Constraints
-    /// * `key_size` must be 1024 or 2048
     /// * `auth_size` must be at most 32
+    /// * an RSA key's `size` must be 1024, 2048, 3072 or 4096
     ///
     /// # Errors
-    /// * if the authentication size is given larger than 32 or if the requested key size is not
-    /// 1024 or 2048, a `WrongParamSize` wrapper error is returned
+    /// * if the authentication size is given larger than 32 or the RSA key size is not one of
+    /// the supported sizes, a `WrongParamSize` wrapper error is returned
+    /// * if `key_params`'s scheme does not belong to its key family (e.g. `KeyParams::Ecc` with
+    /// an RSA scheme, or `KeyParams::Rsa` with `KeySchemeUnion::Ecdsa`), an `InvalidParam`
+    /// wrapper error is returned
     /// * errors are returned if any method calls return an error: `Context::get_random`,
     /// `TransientObjectContext::set_session_attrs`, `Context::create_key`, `Context::load`,
     /// `Context::context_save`, `Context::context_flush`
-    pub fn create_rsa_signing_key(
+    pub fn create_key(
         &mut self,
-        key_size: usize,
+        key_params: KeyParams,
         auth_size: usize,
-    ) -> Result<(TpmsContext, Vec<u8>)> {
+    ) -> Result<(TpmsContext, Option<Vec<u8>>)> {
         if auth_size > 32 {
             return Err(Error::local_error(ErrorKind::WrongParamSize));
         }
-        if key_size != 1024 && key_size != 2048 {
-            return Err(Error::local_error(ErrorKind::WrongParamSize));
-        }
+        let public = key_params_to_public(key_params)?;
+
         let key_auth = if auth_size > 0 {
             self.set_session_attrs()?;
-            self.context.get_random(auth_size)?
+            Some(self.context.get_random(auth_size)?)
         } else {
-            vec![]
+            None
         };
         self.set_session_attrs()?;
         let (key_priv, key_pub) = self.context.create_key(
             self.root_key_handle,
-            &get_rsa_public(false, false, true, key_size.try_into().unwrap()), // should not fail on valid targets, given the checks above
-            &key_auth,
+            &public,
+            key_auth.as_deref().unwrap_or(&[]),
             &[],
             &[],
             &[],
@@ -172,6 +330,53 @@ impl TransientObjectContext {
         Ok((key_context, key_auth))
     }
 
+    /// Create a new RSA signing key, using RSA SSA with SHA256.
+    ///
+    /// A thin wrapper around `create_key` kept for callers that only need the previous, narrower
+    /// interface.
+    ///
+    /// # Constraints
+    /// * `key_size` must be 1024, 2048, 3072 or 4096
+    /// * `auth_size` must be at most 32
+    ///
+    /// # Errors
+    /// * see `TransientObjectContext::create_key`
+    pub fn create_rsa_signing_key(
+        &mut self,
+        key_size: usize,
+        auth_size: usize,
+    ) -> Result<(TpmsContext, Vec<u8>)> {
+        let (key_context, key_auth) = self.create_key(
+            KeyParams::Rsa {
+                size: key_size,
+                scheme: KeySchemeUnion::RsaSsa(TPM2_ALG_SHA256),
+                pub_exponent: 0,
+            },
+            auth_size,
+        )?;
+        Ok((key_context, key_auth.unwrap_or_default()))
+    }
+
+    /// Create a new ECC signing key on the NIST P-256 curve, using ECDSA with SHA256.
+    ///
+    /// A thin wrapper around `create_key` for this common case.
+    ///
+    /// # Constraints
+    /// * `auth_size` must be at most 32
+    ///
+    /// # Errors
+    /// * see `TransientObjectContext::create_key`
+    pub fn create_ecc_signing_key(&mut self, auth_size: usize) -> Result<(TpmsContext, Vec<u8>)> {
+        let (key_context, key_auth) = self.create_key(
+            KeyParams::Ecc {
+                curve: TPM2_ECC_NIST_P256,
+                scheme: KeySchemeUnion::Ecdsa(TPM2_ALG_SHA256),
+            },
+            auth_size,
+        )?;
+        Ok((key_context, key_auth.unwrap_or_default()))
+    }
+
     /// Load a previously generated RSA public key.
     ///
     /// Returns the key context.
@@ -207,7 +412,9 @@ impl TransientObjectContext {
         public.publicArea.unique = pk;
 
         self.set_session_attrs()?;
-        let key_handle = self.context.load_external_public(&public, TPM2_RH_OWNER)?;
+        let key_handle = self
+            .context
+            .load_external_public(&public, self.hierarchy.tpm_handle())?;
 
         self.set_session_attrs()?;
         let key_context = self.context.context_save(key_handle).or_else(|e| {
@@ -243,6 +450,14 @@ impl TransientObjectContext {
                 key.truncate(pub_key.size.try_into().unwrap()); // should not fail on supported targets
                 key
             }
+            PublicIdUnion::Ecc(pub_key) => {
+                let mut x = pub_key.x.buffer.to_vec();
+                x.truncate(pub_key.x.size.try_into().unwrap()); // should not fail on supported targets
+                let mut y = pub_key.y.buffer.to_vec();
+                y.truncate(pub_key.y.size.try_into().unwrap()); // should not fail on supported targets
+                x.extend(y);
+                x
+            }
             _ => return Err(Error::local_error(ErrorKind::UnsupportedParam)),
         };
         self.context.flush_context(key_handle)?;
@@ -252,9 +467,16 @@ impl TransientObjectContext {
 
     /// Sign a digest with an existing key.
     ///
-    /// Takes the key as a parameter, signs and returns the signature.
+    /// Takes the key as a parameter, signs and returns the signature. `scheme` selects the
+    /// signing algorithm and hash (SHA1, SHA256, SHA384 or SHA512) used to build the
+    /// `TPMT_SIG_SCHEME` passed to the TPM, rather than leaving the choice to the key's own
+    /// baked-in scheme via `TPM2_ALG_NULL`; it must be the same scheme the key was created with.
     ///
     /// # Errors
+    /// * if `digest` is not the length produced by `scheme`'s hash algorithm, a `WrongParamSize`
+    /// wrapper error is returned
+    /// * if `scheme` is not a signing scheme (i.e. RSA OAEP), an `InvalidParam` wrapper error is
+    /// returned
     /// * errors are returned if any method calls return an error: `Context::context_load`,
     /// `Context::sign`, `Context::flush_context`, `TransientObjectContext::set_session_attrs`
     /// `Context::set_handle_auth`
@@ -262,8 +484,14 @@ impl TransientObjectContext {
         &mut self,
         key_context: TpmsContext,
         key_auth: &[u8],
+        scheme: KeySchemeUnion,
         digest: &[u8],
     ) -> Result<utils::Signature> {
+        if digest.len() != scheme.digest_size() {
+            return Err(Error::local_error(ErrorKind::WrongParamSize));
+        }
+        let scheme = scheme.sig_scheme()?;
+
         self.set_session_attrs()?;
         let key_handle = self.context.context_load(key_context)?;
         self.context
@@ -273,10 +501,6 @@ impl TransientObjectContext {
                 Err(e)
             })?;
 
-        let scheme = TPMT_SIG_SCHEME {
-            scheme: TPM2_ALG_NULL,
-            details: Default::default(),
-        };
         let validation = TPMT_TK_HASHCHECK {
             tag: TPM2_ST_HASHCHECK,
             hierarchy: TPM2_RH_NULL,
@@ -297,7 +521,8 @@ impl TransientObjectContext {
     /// Verify a signature against a digest.
     ///
     /// Given a digest, a key and a signature, this method returns a `Verified` ticket if the
-    /// verification was successful.
+    /// verification was successful. The scheme and hash algorithm used are those carried by
+    /// `signature` itself, so this already supports any of SHA1, SHA256, SHA384 or SHA512.
     ///
     /// # Errors
     /// * if the verification fails (i.e. the signature is invalid), a TPM error is returned
@@ -329,6 +554,84 @@ impl TransientObjectContext {
         Ok(verified.try_into()?)
     }
 
+    /// Encrypt data with an existing key, using RSA OAEP.
+    ///
+    /// Takes the key as a parameter, encrypts and returns the ciphertext. `scheme` must be the
+    /// `KeySchemeUnion::RsaOaep` scheme the key was created with (see `KeyParams::Rsa`), as the
+    /// TPM requires the hash algorithm given here to match the one baked into the key.
+    ///
+    /// # Errors
+    /// * if `scheme` is not `KeySchemeUnion::RsaOaep`, an `InvalidParam` wrapper error is
+    /// returned
+    /// * errors are returned if any method calls return an error: `Context::context_load`,
+    /// `Context::rsa_encrypt`, `Context::flush_context`, `TransientObjectContext::set_session_attrs`
+    pub fn rsa_encrypt(
+        &mut self,
+        key_context: TpmsContext,
+        scheme: KeySchemeUnion,
+        plaintext: &[u8],
+        label: &[u8],
+    ) -> Result<Vec<u8>> {
+        let scheme = scheme.rsa_decrypt_scheme()?;
+
+        self.set_session_attrs()?;
+        let key_handle = self.context.context_load(key_context)?;
+
+        self.set_session_attrs()?;
+        let ciphertext = self
+            .context
+            .rsa_encrypt(key_handle, plaintext, scheme, label)
+            .or_else(|e| {
+                self.context.flush_context(key_handle)?;
+                Err(e)
+            })?;
+        self.context.flush_context(key_handle)?;
+        Ok(ciphertext)
+    }
+
+    /// Decrypt data with an existing key, using RSA OAEP.
+    ///
+    /// Takes the key as a parameter, decrypts and returns the plaintext. `scheme` must be the
+    /// `KeySchemeUnion::RsaOaep` scheme the key was created with (see `KeyParams::Rsa`), as the
+    /// TPM requires the hash algorithm given here to match the one baked into the key.
+    ///
+    /// # Errors
+    /// * if `scheme` is not `KeySchemeUnion::RsaOaep`, an `InvalidParam` wrapper error is
+    /// returned
+    /// * errors are returned if any method calls return an error: `Context::context_load`,
+    /// `Context::rsa_decrypt`, `Context::flush_context`, `TransientObjectContext::set_session_attrs`
+    /// `Context::set_handle_auth`
+    pub fn rsa_decrypt(
+        &mut self,
+        key_context: TpmsContext,
+        key_auth: &[u8],
+        scheme: KeySchemeUnion,
+        ciphertext: &[u8],
+        label: &[u8],
+    ) -> Result<Vec<u8>> {
+        let scheme = scheme.rsa_decrypt_scheme()?;
+
+        self.set_session_attrs()?;
+        let key_handle = self.context.context_load(key_context)?;
+        self.context
+            .set_handle_auth(key_handle, key_auth)
+            .or_else(|e| {
+                self.context.flush_context(key_handle)?;
+                Err(e)
+            })?;
+
+        self.set_session_attrs()?;
+        let plaintext = self
+            .context
+            .rsa_decrypt(key_handle, ciphertext, scheme, label)
+            .or_else(|e| {
+                self.context.flush_context(key_handle)?;
+                Err(e)
+            })?;
+        self.context.flush_context(key_handle)?;
+        Ok(plaintext)
+    }
+
     /// Sets the encrypt and decrypt flags on the main session used by the context.
     ///
     /// # Errors
@@ -342,3 +645,232 @@ impl TransientObjectContext {
         Ok(())
     }
 }
+
+/// An asymmetric signing or encryption scheme, together with its hash algorithm, selectable when
+/// creating a key via `KeyParams`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySchemeUnion {
+    RsaSsa(TPM2_ALG_ID),
+    RsaPss(TPM2_ALG_ID),
+    RsaOaep(TPM2_ALG_ID),
+    Ecdsa(TPM2_ALG_ID),
+}
+
+impl KeySchemeUnion {
+    /// Whether this scheme can be used by a signing key, as opposed to an encryption/decryption
+    /// key (only RSA OAEP, at present).
+    fn is_signing_scheme(self) -> bool {
+        !matches!(self, KeySchemeUnion::RsaOaep(_))
+    }
+
+    fn hash_alg(self) -> TPM2_ALG_ID {
+        match self {
+            KeySchemeUnion::RsaSsa(hash_alg)
+            | KeySchemeUnion::RsaPss(hash_alg)
+            | KeySchemeUnion::RsaOaep(hash_alg)
+            | KeySchemeUnion::Ecdsa(hash_alg) => hash_alg,
+        }
+    }
+
+    fn rsa_scheme(self) -> TPMT_RSA_SCHEME {
+        match self {
+            KeySchemeUnion::RsaSsa(hash_alg) => TPMT_RSA_SCHEME {
+                scheme: TPM2_ALG_RSASSA,
+                details: TPMU_ASYM_SCHEME {
+                    rsassa: TPMS_SCHEME_HASH { hashAlg: hash_alg },
+                },
+            },
+            KeySchemeUnion::RsaPss(hash_alg) => TPMT_RSA_SCHEME {
+                scheme: TPM2_ALG_RSAPSS,
+                details: TPMU_ASYM_SCHEME {
+                    rsapss: TPMS_SCHEME_HASH { hashAlg: hash_alg },
+                },
+            },
+            KeySchemeUnion::RsaOaep(hash_alg) => TPMT_RSA_SCHEME {
+                scheme: TPM2_ALG_OAEP,
+                details: TPMU_ASYM_SCHEME {
+                    oaep: TPMS_SCHEME_HASH { hashAlg: hash_alg },
+                },
+            },
+            KeySchemeUnion::Ecdsa(_) => unreachable!("ECDSA is not an RSA scheme"),
+        }
+    }
+
+    fn ecc_scheme(self) -> TPMT_ECC_SCHEME {
+        match self {
+            KeySchemeUnion::Ecdsa(hash_alg) => TPMT_ECC_SCHEME {
+                scheme: TPM2_ALG_ECDSA,
+                details: TPMU_ASYM_SCHEME {
+                    ecdsa: TPMS_SCHEME_HASH { hashAlg: hash_alg },
+                },
+            },
+            _ => unreachable!("only ECDSA is supported for ECC keys"),
+        }
+    }
+
+    /// The digest length, in bytes, produced by this scheme's hash algorithm.
+    fn digest_size(self) -> usize {
+        match self.hash_alg() {
+            TPM2_ALG_SHA1 => 20,
+            TPM2_ALG_SHA256 => 32,
+            TPM2_ALG_SHA384 => 48,
+            TPM2_ALG_SHA512 => 64,
+            _ => 0,
+        }
+    }
+
+    /// Build the `TPMT_SIG_SCHEME` used to drive `Context::sign` with this scheme.
+    fn sig_scheme(self) -> Result<TPMT_SIG_SCHEME> {
+        if !self.is_signing_scheme() {
+            return Err(Error::local_error(ErrorKind::InvalidParam));
+        }
+        Ok(match self {
+            KeySchemeUnion::RsaSsa(hash_alg) => TPMT_SIG_SCHEME {
+                scheme: TPM2_ALG_RSASSA,
+                details: TPMU_SIG_SCHEME {
+                    rsassa: TPMS_SCHEME_HASH { hashAlg: hash_alg },
+                },
+            },
+            KeySchemeUnion::RsaPss(hash_alg) => TPMT_SIG_SCHEME {
+                scheme: TPM2_ALG_RSAPSS,
+                details: TPMU_SIG_SCHEME {
+                    rsapss: TPMS_SCHEME_HASH { hashAlg: hash_alg },
+                },
+            },
+            KeySchemeUnion::Ecdsa(hash_alg) => TPMT_SIG_SCHEME {
+                scheme: TPM2_ALG_ECDSA,
+                details: TPMU_SIG_SCHEME {
+                    ecdsa: TPMS_SCHEME_HASH { hashAlg: hash_alg },
+                },
+            },
+            KeySchemeUnion::RsaOaep(_) => unreachable!("rejected by the check above"),
+        })
+    }
+
+    /// Build the `TPMT_RSA_DECRYPT` scheme used to drive `Context::rsa_encrypt`/`rsa_decrypt`.
+    ///
+    /// # Errors
+    /// * if `self` is not `KeySchemeUnion::RsaOaep`, an `InvalidParam` wrapper error is returned
+    fn rsa_decrypt_scheme(self) -> Result<TPMT_RSA_DECRYPT> {
+        match self {
+            KeySchemeUnion::RsaOaep(hash_alg) => Ok(TPMT_RSA_DECRYPT {
+                scheme: TPM2_ALG_OAEP,
+                details: TPMU_ASYM_SCHEME {
+                    oaep: TPMS_SCHEME_HASH { hashAlg: hash_alg },
+                },
+            }),
+            _ => Err(Error::local_error(ErrorKind::InvalidParam)),
+        }
+    }
+}
+
+/// Parameters describing a key to be created via `TransientObjectContext::create_key`.
+#[derive(Debug, Clone, Copy)]
+pub enum KeyParams {
+    Rsa {
+        size: usize,
+        scheme: KeySchemeUnion,
+        pub_exponent: u32,
+    },
+    Ecc {
+        curve: TPM2_ECC_CURVE,
+        scheme: KeySchemeUnion,
+    },
+}
+
+/// Build the `TPM2B_PUBLIC` template for `key_params`.
+///
+/// The object is marked for signing unless `scheme` is a non-signing scheme (RSA OAEP), in which
+/// case it is marked for encryption/decryption instead.
+///
+/// # Errors
+/// * if `key_params`'s scheme does not belong to its key family (e.g. `KeyParams::Ecc` with an
+/// RSA scheme, or `KeyParams::Rsa` with `KeySchemeUnion::Ecdsa`), an `InvalidParam` wrapper
+/// error is returned
+fn key_params_to_public(key_params: KeyParams) -> Result<TPM2B_PUBLIC> {
+    let scheme = match key_params {
+        KeyParams::Rsa { scheme, .. } | KeyParams::Ecc { scheme, .. } => scheme,
+    };
+    let scheme_is_rsa = !matches!(scheme, KeySchemeUnion::Ecdsa(_));
+    let key_params_is_rsa = matches!(key_params, KeyParams::Rsa { .. });
+    if scheme_is_rsa != key_params_is_rsa {
+        return Err(Error::local_error(ErrorKind::InvalidParam));
+    }
+
+    let object_attributes = if scheme.is_signing_scheme() {
+        TPMA_OBJECT_SIGN_ENCRYPT
+    } else {
+        TPMA_OBJECT_DECRYPT
+    } | TPMA_OBJECT_FIXEDTPM
+        | TPMA_OBJECT_FIXEDPARENT
+        | TPMA_OBJECT_SENSITIVEDATAORIGIN
+        | TPMA_OBJECT_USERWITHAUTH;
+
+    let public_area = match key_params {
+        KeyParams::Rsa {
+            size,
+            scheme,
+            pub_exponent,
+        } => {
+            if ![1024_usize, 2048, 3072, 4096].contains(&size) {
+                return Err(Error::local_error(ErrorKind::WrongParamSize));
+            }
+            TPMT_PUBLIC {
+                type_: TPM2_ALG_RSA,
+                nameAlg: TPM2_ALG_SHA256,
+                objectAttributes: object_attributes,
+                authPolicy: Default::default(),
+                parameters: TPMU_PUBLIC_PARMS {
+                    rsaDetail: TPMS_RSA_PARMS {
+                        symmetric: TPMT_SYM_DEF_OBJECT {
+                            algorithm: TPM2_ALG_NULL,
+                            keyBits: Default::default(),
+                            mode: Default::default(),
+                        },
+                        scheme: scheme.rsa_scheme(),
+                        keyBits: size.try_into().unwrap(), // should not fail, given the checks above
+                        exponent: pub_exponent,
+                    },
+                },
+                unique: TPMU_PUBLIC_ID {
+                    rsa: TPM2B_PUBLIC_KEY_RSA {
+                        size: 0,
+                        buffer: [0_u8; 512],
+                    },
+                },
+            }
+        }
+        KeyParams::Ecc { curve, scheme } => TPMT_PUBLIC {
+            type_: TPM2_ALG_ECC,
+            nameAlg: TPM2_ALG_SHA256,
+            objectAttributes: object_attributes,
+            authPolicy: Default::default(),
+            parameters: TPMU_PUBLIC_PARMS {
+                eccDetail: TPMS_ECC_PARMS {
+                    symmetric: TPMT_SYM_DEF_OBJECT {
+                        algorithm: TPM2_ALG_NULL,
+                        keyBits: Default::default(),
+                        mode: Default::default(),
+                    },
+                    scheme: scheme.ecc_scheme(),
+                    curveID: curve,
+                    kdf: TPMT_KDF_SCHEME {
+                        scheme: TPM2_ALG_NULL,
+                        details: Default::default(),
+                    },
+                },
+            },
+            unique: TPMU_PUBLIC_ID {
+                ecc: TPMS_ECC_POINT {
+                    x: Default::default(),
+                    y: Default::default(),
+                },
+            },
+        },
+    };
+
+    Ok(TPM2B_PUBLIC {
+        size: 0,
+        publicArea: public_area,
+    })
+}